@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+/// A single configuration value, as parsed from a config source.
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Table(HashMap<String, Value>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Float(f) => Some(*f),
+            Value::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    fn as_table(&self) -> Option<&HashMap<String, Value>> {
+        match self {
+            Value::Table(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    fn from_toml(v: toml::Value) -> Value {
+        match v {
+            toml::Value::String(s) => Value::String(s),
+            toml::Value::Integer(i) => Value::Int(i),
+            toml::Value::Float(f) => Value::Float(f),
+            toml::Value::Boolean(b) => Value::Bool(b),
+            toml::Value::Table(t) => {
+                Value::Table(t.into_iter().map(|(k, v)| (k, Value::from_toml(v))).collect())
+            }
+            // Arrays and datetimes aren't needed for any of teahin's
+            // settings; store them as an empty table rather than failing
+            // the whole load.
+            toml::Value::Array(_) | toml::Value::Datetime(_) => Value::Table(HashMap::new()),
+        }
+    }
+}
+
+/// A place `Config` can pull key/value pairs from. Sources are merged in
+/// priority order, with later sources overwriting earlier ones.
+pub trait Source {
+    fn collect(&self) -> Result<HashMap<String, Value>>;
+}
+
+/// The hardcoded defaults teahin ships with, applied before any file or
+/// CLI overrides.
+pub struct DefaultSource;
+
+impl Source for DefaultSource {
+    fn collect(&self) -> Result<HashMap<String, Value>> {
+        let mut defaults = HashMap::new();
+        defaults.insert(
+            "temperature".to_string(),
+            Value::Table(HashMap::from([(
+                "unit".to_string(),
+                Value::String("C".to_string()),
+            )])),
+        );
+        Ok(defaults)
+    }
+}
+
+/// A TOML file on disk, e.g. `~/.config/teahin/config.toml`.
+pub struct FileSource {
+    path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileSource { path: path.into() }
+    }
+}
+
+impl Source for FileSource {
+    fn collect(&self) -> Result<HashMap<String, Value>> {
+        let raw = fs::read_to_string(&self.path)
+            .with_context(|| format!("reading config file {:?}", self.path))?;
+        let parsed: toml::Value = raw
+            .parse()
+            .with_context(|| format!("parsing config file {:?}", self.path))?;
+        match Value::from_toml(parsed) {
+            Value::Table(t) => Ok(t),
+            _ => Ok(HashMap::new()),
+        }
+    }
+}
+
+/// Overrides passed on the command line, e.g. `--set temperature.unit=F`.
+pub struct CliSource {
+    overrides: Vec<(String, Value)>,
+}
+
+impl CliSource {
+    pub fn new(overrides: Vec<(String, Value)>) -> Self {
+        CliSource { overrides }
+    }
+}
+
+impl Source for CliSource {
+    fn collect(&self) -> Result<HashMap<String, Value>> {
+        let mut out = HashMap::new();
+        for (path, value) in &self.overrides {
+            insert_path(&mut out, path, value.clone());
+        }
+        Ok(out)
+    }
+}
+
+fn insert_path(table: &mut HashMap<String, Value>, path: &str, value: Value) {
+    let mut parts = path.splitn(2, '.');
+    let head = parts.next().unwrap();
+    match parts.next() {
+        None => {
+            table.insert(head.to_string(), value);
+        }
+        Some(rest) => {
+            let entry = table
+                .entry(head.to_string())
+                .or_insert_with(|| Value::Table(HashMap::new()));
+            // A prior override may have set this segment as a scalar (e.g.
+            // `--set chips.coretemp=1` before `--set chips.coretemp.temp1.label=CPU`).
+            // Promote it to a table rather than silently dropping the later
+            // override, matching `merge`'s own precedent of letting the later
+            // value win on a type conflict.
+            if !matches!(entry, Value::Table(_)) {
+                *entry = Value::Table(HashMap::new());
+            }
+            if let Value::Table(sub) = entry {
+                insert_path(sub, rest, value);
+            }
+        }
+    }
+}
+
+fn merge(base: &mut HashMap<String, Value>, overlay: HashMap<String, Value>) {
+    for (key, value) in overlay {
+        if let Value::Table(overlay_table) = value {
+            if let Some(Value::Table(base_table)) = base.get_mut(&key) {
+                merge(base_table, overlay_table);
+                continue;
+            }
+            base.insert(key, Value::Table(overlay_table));
+        } else {
+            base.insert(key, value);
+        }
+    }
+}
+
+/// Teahin's fully-resolved configuration: built-in defaults, then config
+/// files, then CLI overrides, merged in priority order and frozen once
+/// loaded.
+pub struct Config {
+    values: HashMap<String, Value>,
+}
+
+impl Config {
+    /// Load and merge every source in priority order (later sources win),
+    /// returning an immutable, ready-to-query `Config`.
+    pub fn load(sources: &[Box<dyn Source>]) -> Result<Self> {
+        let mut values = HashMap::new();
+        for source in sources {
+            merge(&mut values, source.collect()?);
+        }
+        Ok(Config { values })
+    }
+
+    /// Look up a dotted path, e.g. `chips.coretemp.temp1.label`.
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        let mut parts = path.split('.').peekable();
+        let mut table = &self.values;
+        loop {
+            let part = parts.next()?;
+            let value = table.get(part)?;
+            if parts.peek().is_none() {
+                return Some(value);
+            }
+            table = value.as_table()?;
+        }
+    }
+
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.get(path)?.as_str()
+    }
+
+    pub fn get_f64(&self, path: &str) -> Option<f64> {
+        self.get(path)?.as_f64()
+    }
+
+    pub fn get_bool(&self, path: &str) -> Option<bool> {
+        self.get(path)?.as_bool()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedSource(HashMap<String, Value>);
+
+    impl Source for FixedSource {
+        fn collect(&self) -> Result<HashMap<String, Value>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn insert_path_nests_dotted_keys() {
+        let mut table = HashMap::new();
+        insert_path(&mut table, "chips.coretemp.temp1.label", Value::String("CPU".into()));
+        let config = Config { values: table };
+        assert_eq!(config.get_str("chips.coretemp.temp1.label"), Some("CPU"));
+    }
+
+    #[test]
+    fn insert_path_promotes_a_scalar_collision_to_a_table() {
+        let mut table = HashMap::new();
+        insert_path(&mut table, "chips.coretemp", Value::Int(1));
+        insert_path(&mut table, "chips.coretemp.temp1.label", Value::String("CPU".into()));
+        let config = Config { values: table };
+        assert_eq!(config.get_str("chips.coretemp.temp1.label"), Some("CPU"));
+    }
+
+    #[test]
+    fn later_source_overrides_earlier_on_scalar_conflict() {
+        let mut base = HashMap::from([("unit".to_string(), Value::String("C".to_string()))]);
+        let overlay = HashMap::from([("unit".to_string(), Value::String("F".to_string()))]);
+        merge(&mut base, overlay);
+        assert_eq!(base.get("unit").and_then(Value::as_str), Some("F"));
+    }
+
+    #[test]
+    fn merge_combines_nested_tables_instead_of_replacing_them() {
+        let mut base = HashMap::from([(
+            "chips".to_string(),
+            Value::Table(HashMap::from([(
+                "coretemp".to_string(),
+                Value::Table(HashMap::from([(
+                    "temp1".to_string(),
+                    Value::String("old".to_string()),
+                )])),
+            )])),
+        )]);
+        let overlay = HashMap::from([(
+            "chips".to_string(),
+            Value::Table(HashMap::from([(
+                "coretemp".to_string(),
+                Value::Table(HashMap::from([(
+                    "temp2".to_string(),
+                    Value::String("new".to_string()),
+                )])),
+            )])),
+        )]);
+        merge(&mut base, overlay);
+        let config = Config { values: base };
+        assert_eq!(config.get_str("chips.coretemp.temp1"), Some("old"));
+        assert_eq!(config.get_str("chips.coretemp.temp2"), Some("new"));
+    }
+
+    #[test]
+    fn config_load_applies_sources_in_priority_order() {
+        let defaults = FixedSource(HashMap::from([(
+            "temperature".to_string(),
+            Value::Table(HashMap::from([("unit".to_string(), Value::String("C".to_string()))])),
+        )]));
+        let overrides = FixedSource(HashMap::from([(
+            "temperature".to_string(),
+            Value::Table(HashMap::from([("unit".to_string(), Value::String("F".to_string()))])),
+        )]));
+        let sources: Vec<Box<dyn Source>> = vec![Box::new(defaults), Box::new(overrides)];
+        let config = Config::load(&sources).unwrap();
+        assert_eq!(config.get_str("temperature.unit"), Some("F"));
+    }
+}
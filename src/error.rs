@@ -0,0 +1,122 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::Utf8Error;
+
+/// Everything that can go wrong reading or parsing a sensor, in one place,
+/// so a single bad chip can be reported and skipped instead of panicking
+/// the whole program.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    BadPath(PathBuf),
+    UnparseableInput { path: PathBuf, raw: String },
+    Utf8(Utf8Error),
+    /// Failures from the config-loading layer, which already carries its
+    /// own rich context via `anyhow`.
+    Config(anyhow::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::BadPath(path) => write!(f, "not a valid sensor path: {:?}", path),
+            Error::UnparseableInput { path, raw } => {
+                write!(f, "could not parse {:?} as a number, got {:?}", path, raw)
+            }
+            Error::Utf8(e) => write!(f, "{}", e),
+            Error::Config(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Utf8(e) => Some(e),
+            Error::Config(e) => Some(e.as_ref()),
+            Error::BadPath(_) | Error::UnparseableInput { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(e: Utf8Error) -> Self {
+        Error::Utf8(e)
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(e: anyhow::Error) -> Self {
+        Error::Config(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Wraps `main`'s result so a failure is reported as `teahin: <error>` and
+/// the process exits non-zero, instead of an `unwrap` panic and backtrace.
+pub struct MainResult(pub Result<()>);
+
+impl MainResult {
+    pub fn report(self) -> std::process::ExitCode {
+        match self.0 {
+            Ok(()) => std::process::ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}: {}", env!("CARGO_PKG_NAME"), e);
+                std::process::ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_path_display_includes_the_path() {
+        let err = Error::BadPath(PathBuf::from("/sys/class/hwmon/hwmon0/weird"));
+        assert_eq!(
+            err.to_string(),
+            "not a valid sensor path: \"/sys/class/hwmon/hwmon0/weird\""
+        );
+    }
+
+    #[test]
+    fn unparseable_input_display_includes_path_and_raw_value() {
+        let err = Error::UnparseableInput {
+            path: PathBuf::from("temp1_input"),
+            raw: "not a number".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "could not parse \"temp1_input\" as a number, got \"not a number\""
+        );
+    }
+
+    #[test]
+    fn io_error_converts_via_from_and_displays_its_source() {
+        let io_err = io::Error::new(io::ErrorKind::NotFound, "missing");
+        let err: Error = io_err.into();
+        assert!(matches!(err, Error::Io(_)));
+        assert_eq!(err.to_string(), "missing");
+    }
+
+    #[test]
+    fn utf8_error_converts_via_from_and_displays_its_source() {
+        let bytes: Vec<u8> = vec![0xff, 0xfe];
+        let utf8_err = std::str::from_utf8(&bytes).unwrap_err();
+        let err: Error = utf8_err.into();
+        assert!(matches!(err, Error::Utf8(_)));
+        assert_eq!(err.to_string(), utf8_err.to_string());
+    }
+}
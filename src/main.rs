@@ -1,57 +1,171 @@
-use anyhow::bail;
+mod config;
+mod error;
+mod path;
+
 use core::f64;
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::collections::HashSet;
 use std::ffi::OsStr;
-use std::fs::{self, DirEntry, File};
+use std::fs::{self, DirEntry};
 use std::io;
-use std::os::unix::fs::FileExt;
 use std::path::Path;
 use std::path::PathBuf;
-use std::str;
+
+use config::{CliSource, Config, DefaultSource, FileSource, Source, Value};
+use error::{Error, MainResult};
 
 struct Hwmon {
     name: String,
+    device: Option<PathBuf>,
     inputs: Vec<Input>,
 }
 
 impl Hwmon {
-    fn get_all() -> io::Result<Vec<Self>> {
+    /// Load every hwmon chip under `/sys/class/hwmon/`. A chip that fails
+    /// to load (e.g. a malformed `name` file) is warned about and skipped
+    /// rather than aborting discovery of every other chip.
+    fn get_all(config: &Config) -> error::Result<Vec<Self>> {
         let hwmon_dir = Path::new("/sys/class/hwmon/");
         let mut hwmons: Vec<Self> = Vec::new();
-        for dent in fs::read_dir(&hwmon_dir)?.collect::<io::Result<Vec<DirEntry>>>()? {
+        let mut seen_devices: HashSet<PathBuf> = HashSet::new();
+        for dent in fs::read_dir(hwmon_dir)?.collect::<io::Result<Vec<DirEntry>>>()? {
             let abs_path = dent.path();
-            hwmons.push(Self::load(&abs_path)?);
+            let device = path::resolve_device(&abs_path).ok();
+            if let Some(ref d) = device {
+                if !seen_devices.insert(d.clone()) {
+                    continue;
+                }
+            }
+            match Self::load(&abs_path, device, config) {
+                Ok(hwmon) => hwmons.push(hwmon),
+                Err(e) => eprintln!("warning: skipping {:?}: {}", abs_path, e),
+            }
         }
         Ok(hwmons)
     }
 
-    fn load(dir_abs_path: &Path) -> io::Result<Self> {
+    /// Load a single hwmon chip's inputs. An individual `*_input` that
+    /// fails to parse is warned about and skipped rather than failing the
+    /// whole chip.
+    fn load(dir_abs_path: &Path, device: Option<PathBuf>, config: &Config) -> error::Result<Self> {
         let mut name_path = dir_abs_path.to_path_buf();
         name_path.push("name");
-        let name = fs::read_to_string(name_path)?;
+        let name = fs::read_to_string(name_path)?.trim().to_string();
+        if config.get_bool(&format!("chips.{}.enabled", name)) == Some(false) {
+            return Ok(Hwmon {
+                name,
+                device,
+                inputs: Vec::new(),
+            });
+        }
         let mut inputs = Vec::new();
         for dent in fs::read_dir(dir_abs_path)?.collect::<io::Result<Vec<DirEntry>>>()? {
             match dent.file_name().to_str() {
                 None => continue,
                 Some(n) => {
                     if n.ends_with("_input") {
-                        inputs.push(Input::new(&dent.path()))
+                        match Input::new(&dent.path(), &name, config) {
+                            Ok(Some(input)) => inputs.push(input),
+                            Ok(None) => {}
+                            Err(e) => {
+                                eprintln!("warning: skipping {:?}: {}", dent.path(), e)
+                            }
+                        }
                     }
                 }
             }
         }
         Ok(Hwmon {
             name,
-            inputs: Vec::new(),
+            device,
+            inputs,
         })
     }
 }
 
 trait Updateable {
-    fn update(&self) -> f64;
+    fn update(&self) -> error::Result<f64>;
     fn unit(&self) -> &str;
     fn label(&self) -> &str;
+    fn status(&self, value: f64) -> Status;
+}
+
+/// An at-a-glance health read for an `Input`, derived from the current
+/// value and its `Thresholds`.
+#[derive(Debug, PartialEq, Eq)]
+enum Status {
+    Low,
+    Normal,
+    High,
+    Critical,
+}
+
+/// The sibling `*_min`/`*_max`/`*_crit`/`*_alarm` attributes for an input,
+/// when the chip exposes them. Values are scaled the same way as the
+/// input's own value (e.g. milli-degrees to degrees for `Type::Temp`).
+#[derive(Default)]
+struct Thresholds {
+    min: Option<f64>,
+    max: Option<f64>,
+    crit: Option<f64>,
+    alarm: Option<bool>,
+}
+
+impl Thresholds {
+    /// Probe the sibling threshold attributes for an input. A config
+    /// override at `<key>.min`/`<key>.max`/`<key>.crit` (e.g. via
+    /// `--set chips.coretemp.temp1.max=85`) takes priority over whatever
+    /// the chip itself reports.
+    fn probe(
+        input_abs_path: &Path,
+        name: &str,
+        is_temp: bool,
+        config: &Config,
+        key: &str,
+    ) -> error::Result<Self> {
+        let sibling = |suffix: &str| {
+            let mut p = input_abs_path.to_path_buf();
+            p.pop();
+            p.push(format!("{}_{}", name, suffix));
+            p
+        };
+        let scale = |v: f64| if is_temp { v / 1000f64 } else { v };
+        let resolve = |suffix: &str| -> error::Result<Option<f64>> {
+            if let Some(v) = config.get_f64(&format!("{}.{}", key, suffix)) {
+                return Ok(Some(v));
+            }
+            Ok(read_optional_attr(&sibling(suffix))?.map(scale))
+        };
+        Ok(Thresholds {
+            min: resolve("min")?,
+            max: resolve("max")?,
+            crit: resolve("crit")?,
+            alarm: read_optional_attr(&sibling("alarm"))?.map(|v| v != 0f64),
+        })
+    }
+}
+
+/// Read a sysfs attribute and parse it as a number, trimming the trailing
+/// newline sysfs attributes are conventionally written with.
+fn read_attr(path: &Path) -> error::Result<f64> {
+    let bytes = fs::read(path)?;
+    let raw = std::str::from_utf8(&bytes)?;
+    let trimmed = raw.trim();
+    trimmed.parse::<f64>().map_err(|_| Error::UnparseableInput {
+        path: path.to_path_buf(),
+        raw: trimmed.to_string(),
+    })
+}
+
+/// Like `read_attr`, but treats a missing attribute as `None` rather than
+/// an error, since not every chip exposes every threshold.
+fn read_optional_attr(path: &Path) -> error::Result<Option<f64>> {
+    match read_attr(path) {
+        Ok(v) => Ok(Some(v)),
+        Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
 }
 
 enum Type {
@@ -61,23 +175,64 @@ enum Type {
     Other(Option<String>),
 }
 
+/// The unit temperatures are reported in, as set by `temperature.unit` in
+/// the loaded `Config`. Defaults to Celsius, which is what sysfs reports.
+enum TempUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TempUnit {
+    fn from_config(config: &Config) -> Self {
+        match config.get_str("temperature.unit") {
+            Some("F") => TempUnit::Fahrenheit,
+            Some("K") => TempUnit::Kelvin,
+            _ => TempUnit::Celsius,
+        }
+    }
+
+    fn convert(&self, celsius: f64) -> f64 {
+        match self {
+            TempUnit::Celsius => celsius,
+            TempUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+            TempUnit::Kelvin => celsius + 273.15,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            TempUnit::Celsius => "°C",
+            TempUnit::Fahrenheit => "°F",
+            TempUnit::Kelvin => "K",
+        }
+    }
+}
+
 struct Input {
-    f: File,
+    path: PathBuf,
     label: String,
+    unit_override: Option<String>,
+    temp_unit: TempUnit,
     typ: Type,
+    thresholds: Thresholds,
 }
 
 impl Input {
-    fn new(input_abs_path: &Path) -> anyhow::Result<Self> {
+    /// Parse the `*_input` file at `input_abs_path` into an `Input`,
+    /// consulting `config` for this chip's label/unit/enabled overrides
+    /// (keyed by `chips.<chip_name>.<input_name>`). Returns `Ok(None)` if
+    /// the config suppresses this input.
+    fn new(input_abs_path: &Path, chip_name: &str, config: &Config) -> error::Result<Option<Self>> {
         lazy_static! {
             static ref RE: Regex = Regex::new(r"([A-z]+)(\d+)_").unwrap();
         };
         let input_file_name = match input_abs_path.file_name().and_then(OsStr::to_str) {
-            None => bail!("incorrect path {:?}", input_abs_path),
+            None => return Err(Error::BadPath(input_abs_path.to_path_buf())),
             Some(s) => s,
         };
         let parsed: regex::Captures<'_> = match RE.captures(input_file_name) {
-            None => bail!("incorrect path to input {:?}", input_abs_path),
+            None => return Err(Error::BadPath(input_abs_path.to_path_buf())),
             Some(c) => c,
         };
         let typ_name = parsed.get(1).unwrap();
@@ -89,42 +244,42 @@ impl Input {
         };
         let idx = parsed.get(2).unwrap();
         let name = &input_file_name[0..idx.end()];
+        let key = format!("chips.{}.{}", chip_name, name);
+        if config.get_bool(&format!("{}.enabled", key)) == Some(false) {
+            return Ok(None);
+        }
         let mut label_path = input_abs_path.to_path_buf();
         label_path.pop();
         label_path.push(format!("{}_label", name));
-        let label = match fs::read_to_string(label_path) {
-            Err(e) => match e.kind() {
-                io::ErrorKind::NotFound => name.to_string(),
-                _ => return Err(e.into()),
+        let label = match config.get_str(&format!("{}.label", key)) {
+            Some(s) => s.to_string(),
+            None => match fs::read_to_string(label_path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => name.to_string(),
+                Err(e) => return Err(e.into()),
+                Ok(s) => s.trim_end_matches('\n').to_string(),
             },
-            Ok(mut s) => {
-                let c = s.pop();
-                assert_eq!(c, Some('\n'));
-                s
-            }
         };
-        let f = File::open(input_abs_path)?;
-        Ok(Input { f, label, typ })
+        let unit_override = config.get_str(&format!("{}.unit", key)).map(str::to_string);
+        let is_temp = matches!(typ, Type::Temp);
+        let thresholds = Thresholds::probe(input_abs_path, name, is_temp, config, &key)?;
+        Ok(Some(Input {
+            path: input_abs_path.to_path_buf(),
+            label,
+            unit_override,
+            temp_unit: TempUnit::from_config(config),
+            typ,
+            thresholds,
+        }))
     }
 }
 
 impl Updateable for Input {
-    fn update(&self) -> f64 {
-        let mut buf = [0u8; 4096];
-        match self.f.read_at(&mut buf, 0) {
-            Err(e) => {
-                eprintln!("{}", e);
-                0f64
-            }
-            Ok(n) => {
-                let s = str::from_utf8(&buf[0..n - 1]).unwrap();
-                let r = s.parse::<u32>().unwrap() as f64;
-                match self.typ {
-                    Type::Temp => r / 1000f64,
-                    _ => r,
-                }
-            }
-        }
+    fn update(&self) -> error::Result<f64> {
+        let value = read_attr(&self.path)?;
+        Ok(match self.typ {
+            Type::Temp => self.temp_unit.convert(value / 1000f64),
+            _ => value,
+        })
     }
 
     fn label(&self) -> &str {
@@ -133,25 +288,282 @@ impl Updateable for Input {
 
     fn unit(&self) -> &str {
         use Type::*;
+        if let Some(ref u) = self.unit_override {
+            return u;
+        }
         match self.typ {
             Voltage => "V",
             Fan => " RPM",
-            Temp => "Â°C",
+            Temp => self.temp_unit.symbol(),
             Other(ref m) => match m {
                 None => "",
                 Some(ref s) => s,
             },
         }
     }
+
+    fn status(&self, value: f64) -> Status {
+        // Thresholds are stored before the temperature-unit conversion
+        // (degrees Celsius for temps), so bring them into the unit `update()`
+        // just reported in.
+        let in_reported_unit = |celsius_or_raw: f64| match self.typ {
+            Type::Temp => self.temp_unit.convert(celsius_or_raw),
+            _ => celsius_or_raw,
+        };
+        if self.thresholds.alarm == Some(true) {
+            return Status::Critical;
+        }
+        if let Some(crit) = self.thresholds.crit {
+            if value >= in_reported_unit(crit) {
+                return Status::Critical;
+            }
+        }
+        if let Some(max) = self.thresholds.max {
+            if value >= in_reported_unit(max) {
+                return Status::High;
+            }
+        }
+        if let Some(min) = self.thresholds.min {
+            if value <= in_reported_unit(min) {
+                return Status::Low;
+            }
+        }
+        Status::Normal
+    }
+}
+
+fn config_sources(cli_overrides: Vec<(String, Value)>) -> Vec<Box<dyn Source>> {
+    let mut sources: Vec<Box<dyn Source>> = vec![Box::new(DefaultSource)];
+    let xdg_path = dirs_config_path();
+    if xdg_path.exists() {
+        sources.push(Box::new(FileSource::new(xdg_path)));
+    }
+    if !cli_overrides.is_empty() {
+        sources.push(Box::new(CliSource::new(cli_overrides)));
+    }
+    sources
+}
+
+/// Split the process arguments into a positional path (if any) and a list
+/// of `--set key=value` config overrides, the highest-priority layer in
+/// `config_sources`.
+fn parse_args(mut args: impl Iterator<Item = String>) -> (Option<String>, Vec<(String, Value)>) {
+    let mut target = None;
+    let mut overrides = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--set" {
+            if let Some(kv) = args.next() {
+                if let Some((key, value)) = kv.split_once('=') {
+                    overrides.push((key.to_string(), parse_cli_value(value)));
+                }
+            }
+        } else if target.is_none() {
+            target = Some(arg);
+        }
+    }
+    (target, overrides)
+}
+
+/// Parse a `--set` value as the most specific TOML-ish type it looks like:
+/// bool, then int, then float, falling back to a plain string.
+fn parse_cli_value(raw: &str) -> Value {
+    if let Ok(b) = raw.parse::<bool>() {
+        Value::Bool(b)
+    } else if let Ok(i) = raw.parse::<i64>() {
+        Value::Int(i)
+    } else if let Ok(f) = raw.parse::<f64>() {
+        Value::Float(f)
+    } else {
+        Value::String(raw.to_string())
+    }
+}
+
+fn dirs_config_path() -> PathBuf {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    base.join("teahin").join("config.toml")
+}
+
+/// Find the chip name an input belongs to by walking up from its
+/// directory looking for a sibling `name` file, the same attribute
+/// `Hwmon::load` reads. Falls back to `"cli"` if none is found (e.g. the
+/// input was copied somewhere outside a real hwmon tree).
+fn chip_name_for(input_path: &Path) -> String {
+    let mut dir = input_path.parent();
+    while let Some(d) = dir {
+        if let Ok(name) = fs::read_to_string(d.join("name")) {
+            return name.trim().to_string();
+        }
+        dir = d.parent();
+    }
+    "cli".to_string()
+}
+
+/// Run against a user-supplied path: a single `*_input` file, or a
+/// directory to be walked recursively for every `*_input` it contains.
+fn run_path(target: &Path, config: &Config) -> error::Result<()> {
+    for input_path in path::collect_inputs(target)? {
+        let chip_name = chip_name_for(&input_path);
+        match Input::new(&input_path, &chip_name, config) {
+            Ok(Some(input)) => match input.update() {
+                Ok(value) => println!(
+                    "{}: {}{} ({:?})",
+                    input.label(),
+                    value,
+                    input.unit(),
+                    input.status(value)
+                ),
+                Err(e) => eprintln!("{}: {}", input.label(), e),
+            },
+            Ok(None) => {}
+            Err(e) => eprintln!("warning: skipping {:?}: {}", input_path, e),
+        }
+    }
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input =
-        Input::new(Path::new("/sys/devices/platform/coretemp.0/hwmon/hwmon4/temp1_input").as_ref())
-            .unwrap();
-    println!("{}: {}{}", input.label(), input.update(), input.unit());
+fn run() -> error::Result<()> {
+    let (target, cli_overrides) = parse_args(std::env::args().skip(1));
+    let config = Config::load(&config_sources(cli_overrides))?;
+
+    if let Some(target) = target {
+        return run_path(Path::new(&target), &config);
+    }
 
-    let hms = Hwmon::get_all().unwrap();
+    for hwmon in Hwmon::get_all(&config)? {
+        let device = hwmon
+            .device
+            .as_deref()
+            .and_then(Path::to_str)
+            .unwrap_or("?");
+        for input in &hwmon.inputs {
+            match input.update() {
+                Ok(value) => println!(
+                    "[{} @ {}] {}: {}{} ({:?})",
+                    hwmon.name,
+                    device,
+                    input.label(),
+                    value,
+                    input.unit(),
+                    input.status(value)
+                ),
+                Err(e) => eprintln!("[{} @ {}] {}: {}", hwmon.name, device, input.label(), e),
+            }
+        }
+    }
 
     Ok(())
 }
+
+fn main() -> std::process::ExitCode {
+    MainResult(run()).report()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_with(typ: Type, thresholds: Thresholds) -> Input {
+        Input {
+            path: PathBuf::from("temp1_input"),
+            label: "test".to_string(),
+            unit_override: None,
+            temp_unit: TempUnit::Celsius,
+            typ,
+            thresholds,
+        }
+    }
+
+    #[test]
+    fn status_is_normal_within_all_thresholds() {
+        let input = input_with(
+            Type::Temp,
+            Thresholds {
+                min: Some(10.0),
+                max: Some(80.0),
+                crit: Some(90.0),
+                alarm: None,
+            },
+        );
+        assert_eq!(input.status(50.0), Status::Normal);
+    }
+
+    #[test]
+    fn status_is_high_at_the_max_boundary() {
+        let input = input_with(
+            Type::Temp,
+            Thresholds {
+                min: None,
+                max: Some(80.0),
+                crit: Some(90.0),
+                alarm: None,
+            },
+        );
+        assert_eq!(input.status(80.0), Status::High);
+    }
+
+    #[test]
+    fn status_is_critical_at_the_crit_boundary() {
+        let input = input_with(
+            Type::Temp,
+            Thresholds {
+                min: None,
+                max: Some(80.0),
+                crit: Some(90.0),
+                alarm: None,
+            },
+        );
+        assert_eq!(input.status(90.0), Status::Critical);
+    }
+
+    #[test]
+    fn status_is_low_at_the_min_boundary() {
+        let input = input_with(
+            Type::Temp,
+            Thresholds {
+                min: Some(10.0),
+                max: Some(80.0),
+                crit: Some(90.0),
+                alarm: None,
+            },
+        );
+        assert_eq!(input.status(10.0), Status::Low);
+    }
+
+    #[test]
+    fn status_is_critical_when_alarm_is_set_regardless_of_value() {
+        let input = input_with(
+            Type::Temp,
+            Thresholds {
+                min: Some(10.0),
+                max: Some(80.0),
+                crit: None,
+                alarm: Some(true),
+            },
+        );
+        assert_eq!(input.status(50.0), Status::Critical);
+    }
+
+    #[test]
+    fn parse_cli_value_picks_the_most_specific_type() {
+        assert!(matches!(parse_cli_value("true"), Value::Bool(true)));
+        assert!(matches!(parse_cli_value("42"), Value::Int(42)));
+        assert!(matches!(parse_cli_value("3.5"), Value::Float(f) if f == 3.5));
+        assert!(matches!(parse_cli_value("CPU"), Value::String(s) if s == "CPU"));
+    }
+
+    #[test]
+    fn parse_args_splits_target_and_set_overrides() {
+        let args = vec![
+            "some/path".to_string(),
+            "--set".to_string(),
+            "chips.coretemp.temp1.max=85".to_string(),
+        ];
+        let (target, overrides) = parse_args(args.into_iter());
+        assert_eq!(target, Some("some/path".to_string()));
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].0, "chips.coretemp.temp1.max");
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Resolve a hwmon chip's `device` symlink to its real, canonical path
+/// (e.g. a PCI or platform device path), so chips backed by the same
+/// physical device can be deduplicated and reported by something more
+/// meaningful than the hwmon index.
+pub fn resolve_device(hwmon_dir: &Path) -> io::Result<PathBuf> {
+    let device_link = hwmon_dir.join("device");
+    let target = fs::read_link(&device_link)?;
+    let abs = if target.is_absolute() {
+        target
+    } else {
+        hwmon_dir.join(target)
+    };
+    fs::canonicalize(abs)
+}
+
+/// Collect every `*_input` file reachable from `root`: if `root` is itself
+/// a `*_input` file, returns just that file; if it's a directory, recurses
+/// into it (following symlinks, since a hwmon class entry usually *is*
+/// one) and collects every `*_input` found anywhere underneath. The
+/// result is deduplicated and sorted for stable output.
+///
+/// Real hwmon directories carry `subsystem`/`device` symlinks that loop
+/// back to an ancestor; each directory's canonical path is tracked so a
+/// cycle is detected and skipped instead of recursing until the kernel's
+/// symlink-loop cap trips with `ELOOP`. A subtree that can't be read (e.g.
+/// a permission error) is likewise warned about and skipped rather than
+/// aborting the whole walk.
+pub fn collect_inputs(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    let mut visited = HashSet::new();
+    walk(root, &mut inputs, &mut visited);
+    inputs.sort();
+    inputs.dedup();
+    Ok(inputs)
+}
+
+fn walk(path: &Path, inputs: &mut Vec<PathBuf>, visited: &mut HashSet<PathBuf>) {
+    let metadata = match fs::metadata(path) {
+        Ok(m) => m,
+        Err(e) => return eprintln!("warning: skipping {:?}: {}", path, e),
+    };
+    if metadata.is_dir() {
+        let canonical = match fs::canonicalize(path) {
+            Ok(c) => c,
+            Err(e) => return eprintln!("warning: skipping {:?}: {}", path, e),
+        };
+        if !visited.insert(canonical) {
+            return;
+        }
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => return eprintln!("warning: skipping {:?}: {}", path, e),
+        };
+        for dent in entries {
+            match dent {
+                Ok(dent) => walk(&dent.path(), inputs, visited),
+                Err(e) => eprintln!("warning: skipping an entry in {:?}: {}", path, e),
+            }
+        }
+    } else if path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with("_input"))
+    {
+        inputs.push(path.to_path_buf());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("teahin-path-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn collect_inputs_on_a_bare_input_file_returns_just_that_file() {
+        let dir = tmp_dir("bare-file");
+        let input = dir.join("temp1_input");
+        fs::write(&input, "42000").unwrap();
+        let found = collect_inputs(&input).unwrap();
+        assert_eq!(found, vec![input]);
+    }
+
+    #[test]
+    fn collect_inputs_recurses_into_a_directory() {
+        let dir = tmp_dir("recurse");
+        fs::write(dir.join("temp1_input"), "42000").unwrap();
+        fs::write(dir.join("name"), "coretemp").unwrap();
+        let sub = dir.join("hwmon0");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join("temp2_input"), "43000").unwrap();
+
+        let mut found = collect_inputs(&dir).unwrap();
+        found.sort();
+        let mut expected = vec![dir.join("temp1_input"), sub.join("temp2_input")];
+        expected.sort();
+        assert_eq!(found, expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn collect_inputs_skips_a_symlink_cycle_instead_of_erroring() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tmp_dir("cycle");
+        let hwmon0 = dir.join("hwmon0");
+        fs::create_dir_all(&hwmon0).unwrap();
+        fs::write(hwmon0.join("temp1_input"), "42000").unwrap();
+        // A `subsystem` symlink looping back up to the parent, mimicking a
+        // real hwmon class/device symlink pair.
+        symlink(&dir, hwmon0.join("subsystem")).unwrap();
+
+        let found = collect_inputs(&dir).unwrap();
+        assert_eq!(found, vec![hwmon0.join("temp1_input")]);
+    }
+}